@@ -0,0 +1,399 @@
+//! Optional dissection of the opaque bytes captured in packet blocks (for
+//! example `EnhancedPacketBlock.data`) into the link, network and transport
+//! headers they encode, given the owning interface's `link_type`.
+//!
+//! This module is entirely separate from the block readers in the crate
+//! root: it borrows from an already-parsed block's byte slice and never
+//! touches a `Reader`, so the core parser stays dissection-free and callers
+//! who don't need it can simply never call `Frame::dissect`.
+
+use std::io::net::ip::IpAddr;
+
+/// `link_type` value for Ethernet, as recorded in
+/// `InterfaceDescriptionBlock::link_type`.
+pub const LINKTYPE_ETHERNET: u16 = 1;
+
+const ETHERTYPE_IPV4: u16 = 0x0800;
+const ETHERTYPE_IPV6: u16 = 0x86DD;
+
+const IP_PROTO_TCP: u8 = 6;
+const IP_PROTO_UDP: u8 = 17;
+
+/// A zero-copy view of a captured packet, dissected as far as `link_type`
+/// and the bytes allow. Each layer is `None` if the link type is unknown,
+/// the ethertype/protocol isn't recognized, or the bytes are too short to
+/// hold a header - callers always still have the raw bytes to fall back on.
+#[derive(Show)]
+pub struct Frame<'a> {
+    pub ethernet: Option<EthernetFrame<'a>>,
+    pub network: Option<NetworkPacket<'a>>,
+    pub transport: Option<TransportSegment<'a>>
+}
+
+impl<'a> Frame<'a> {
+    /// Dissects `data`, captured on an interface whose `link_type` is
+    /// `link_type`, as far as the headers allow.
+    pub fn dissect(data: &'a [u8], link_type: u16) -> Frame<'a> {
+        let ethernet = match link_type {
+            LINKTYPE_ETHERNET => EthernetFrame::parse(data),
+            _ => None
+        };
+
+        let network = ethernet.as_ref()
+            .and_then(|eth| NetworkPacket::parse(eth.ethertype, eth.payload));
+
+        let transport = network.as_ref()
+            .and_then(|net| TransportSegment::parse(net));
+
+        Frame {
+            ethernet: ethernet,
+            network: network,
+            transport: transport
+        }
+    }
+}
+
+/// An Ethernet II header: six-byte MAC addresses and an ethertype.
+#[derive(Show)]
+pub struct EthernetFrame<'a> {
+    pub destination: [u8; 6],
+    pub source: [u8; 6],
+    pub ethertype: u16,
+    pub payload: &'a [u8]
+}
+
+impl<'a> EthernetFrame<'a> {
+    /// Parses an Ethernet II header, or returns `None` if `data` is
+    /// shorter than one.
+    pub fn parse(data: &'a [u8]) -> Option<EthernetFrame<'a>> {
+        if data.len() < 14 {
+            return None;
+        }
+
+        let mut destination = [0u8; 6];
+        let mut source = [0u8; 6];
+
+        for i in range(0u, 6) {
+            destination[i] = data[i];
+            source[i] = data[6 + i];
+        }
+
+        let ethertype = ((data[12] as u16) << 8) | (data[13] as u16);
+
+        Some(EthernetFrame {
+            destination: destination,
+            source: source,
+            ethertype: ethertype,
+            payload: data[14..]
+        })
+    }
+}
+
+/// The network-layer header, parsed from an Ethernet payload's ethertype.
+#[derive(Show)]
+pub enum NetworkPacket<'a> {
+    Ipv4(Ipv4Packet<'a>),
+    Ipv6(Ipv6Packet<'a>)
+}
+
+impl<'a> NetworkPacket<'a> {
+    fn parse(ethertype: u16, data: &'a [u8]) -> Option<NetworkPacket<'a>> {
+        match ethertype {
+            ETHERTYPE_IPV4 => Ipv4Packet::parse(data).map(NetworkPacket::Ipv4),
+            ETHERTYPE_IPV6 => Ipv6Packet::parse(data).map(NetworkPacket::Ipv6),
+            _ => None
+        }
+    }
+
+    /// The protocol number carried in the next header (IPv4's `protocol`,
+    /// IPv6's `next_header`) along with the bytes that follow it.
+    fn transport_payload(&self) -> (u8, &'a [u8]) {
+        match *self {
+            NetworkPacket::Ipv4(ref packet) => (packet.protocol, packet.payload),
+            NetworkPacket::Ipv6(ref packet) => (packet.next_header, packet.payload)
+        }
+    }
+}
+
+/// An IPv4 header, with the header checksum already validated.
+#[derive(Show)]
+pub struct Ipv4Packet<'a> {
+    pub version: u8,
+    pub ihl: u8,
+    pub total_length: u16,
+    pub protocol: u8,
+    pub checksum_valid: bool,
+    pub source: IpAddr,
+    pub destination: IpAddr,
+    pub payload: &'a [u8]
+}
+
+impl<'a> Ipv4Packet<'a> {
+    /// Parses an IPv4 header, or returns `None` if `data` doesn't hold a
+    /// complete, well-formed one (wrong version, or a header shorter than
+    /// 20 bytes or than its IHL). `total_length` is not checked against
+    /// `data`, so a packet cut short by the capture still parses.
+    pub fn parse(data: &'a [u8]) -> Option<Ipv4Packet<'a>> {
+        if data.len() < 20 {
+            return None;
+        }
+
+        let version = data[0] >> 4;
+        let ihl = data[0] & 0xF;
+        let header_len = (ihl as uint) * 4;
+
+        if version != 4 || header_len < 20 || data.len() < header_len {
+            return None;
+        }
+
+        let total_length = ((data[2] as u16) << 8) | (data[3] as u16);
+        let protocol = data[9];
+
+        let source = IpAddr::Ipv4Addr(data[12], data[13], data[14], data[15]);
+        let destination = IpAddr::Ipv4Addr(data[16], data[17], data[18], data[19]);
+
+        let checksum_valid = internet_checksum(data[0..header_len]) == 0;
+
+        Some(Ipv4Packet {
+            version: version,
+            ihl: ihl,
+            total_length: total_length,
+            protocol: protocol,
+            checksum_valid: checksum_valid,
+            source: source,
+            destination: destination,
+            payload: data[header_len..]
+        })
+    }
+}
+
+/// An IPv6 header. IPv6 has no header checksum of its own.
+#[derive(Show)]
+pub struct Ipv6Packet<'a> {
+    pub payload_length: u16,
+    pub next_header: u8,
+    pub hop_limit: u8,
+    pub source: IpAddr,
+    pub destination: IpAddr,
+    pub payload: &'a [u8]
+}
+
+impl<'a> Ipv6Packet<'a> {
+    /// Parses a fixed IPv6 header, or returns `None` if `data` doesn't hold
+    /// a complete one.
+    pub fn parse(data: &'a [u8]) -> Option<Ipv6Packet<'a>> {
+        if data.len() < 40 {
+            return None;
+        }
+
+        if data[0] >> 4 != 6 {
+            return None;
+        }
+
+        let payload_length = ((data[4] as u16) << 8) | (data[5] as u16);
+        let next_header = data[6];
+        let hop_limit = data[7];
+
+        let source = read_ipv6_addr(data[8..24]);
+        let destination = read_ipv6_addr(data[24..40]);
+
+        Some(Ipv6Packet {
+            payload_length: payload_length,
+            next_header: next_header,
+            hop_limit: hop_limit,
+            source: source,
+            destination: destination,
+            payload: data[40..]
+        })
+    }
+}
+
+fn read_ipv6_addr(bytes: &[u8]) -> IpAddr {
+    let mut segments = [0u16; 8];
+
+    for i in range(0u, 8) {
+        segments[i] = ((bytes[i * 2] as u16) << 8) | (bytes[i * 2 + 1] as u16);
+    }
+
+    IpAddr::Ipv6Addr(segments[0], segments[1], segments[2], segments[3],
+                      segments[4], segments[5], segments[6], segments[7])
+}
+
+/// The transport-layer header, parsed from a network packet's protocol
+/// number.
+#[derive(Show)]
+pub enum TransportSegment<'a> {
+    Tcp(TcpSegment<'a>),
+    Udp(UdpDatagram<'a>)
+}
+
+impl<'a> TransportSegment<'a> {
+    fn parse(network: &NetworkPacket<'a>) -> Option<TransportSegment<'a>> {
+        let (protocol, payload) = network.transport_payload();
+
+        match protocol {
+            IP_PROTO_TCP => TcpSegment::parse(payload).map(TransportSegment::Tcp),
+            IP_PROTO_UDP => UdpDatagram::parse(payload).map(TransportSegment::Udp),
+            _ => None
+        }
+    }
+}
+
+/// A TCP segment header, exposing just the ports: the data offset is
+/// validated to locate `payload`, but the flags, sequence numbers and
+/// options aren't parsed.
+#[derive(Show)]
+pub struct TcpSegment<'a> {
+    pub source_port: u16,
+    pub destination_port: u16,
+    pub payload: &'a [u8]
+}
+
+impl<'a> TcpSegment<'a> {
+    pub fn parse(data: &'a [u8]) -> Option<TcpSegment<'a>> {
+        if data.len() < 20 {
+            return None;
+        }
+
+        let source_port = ((data[0] as u16) << 8) | (data[1] as u16);
+        let destination_port = ((data[2] as u16) << 8) | (data[3] as u16);
+        let data_offset = ((data[12] >> 4) as uint) * 4;
+
+        if data_offset < 20 || data.len() < data_offset {
+            return None;
+        }
+
+        Some(TcpSegment {
+            source_port: source_port,
+            destination_port: destination_port,
+            payload: data[data_offset..]
+        })
+    }
+}
+
+/// A UDP datagram header.
+#[derive(Show)]
+pub struct UdpDatagram<'a> {
+    pub source_port: u16,
+    pub destination_port: u16,
+    pub payload: &'a [u8]
+}
+
+impl<'a> UdpDatagram<'a> {
+    pub fn parse(data: &'a [u8]) -> Option<UdpDatagram<'a>> {
+        if data.len() < 8 {
+            return None;
+        }
+
+        let source_port = ((data[0] as u16) << 8) | (data[1] as u16);
+        let destination_port = ((data[2] as u16) << 8) | (data[3] as u16);
+
+        Some(UdpDatagram {
+            source_port: source_port,
+            destination_port: destination_port,
+            payload: data[8..]
+        })
+    }
+}
+
+/// The one's-complement Internet checksum (RFC 1071) of `data`: zero when
+/// `data` includes a correctly computed checksum field.
+fn internet_checksum(data: &[u8]) -> u16 {
+    let mut sum = 0u32;
+    let mut i = 0u;
+
+    while i + 1 < data.len() {
+        sum += ((data[i] as u32) << 8) | (data[i + 1] as u32);
+        i += 2;
+    }
+
+    if i < data.len() {
+        sum += (data[i] as u32) << 8;
+    }
+
+    while sum >> 16 != 0 {
+        sum = (sum & 0xFFFF) + (sum >> 16);
+    }
+
+    !(sum as u16)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Builds an Ethernet + IPv4 + TCP frame with a correct IPv4 header
+    /// checksum, for tests to dissect.
+    fn ethernet_ipv4_tcp_frame() -> Vec<u8> {
+        let mut data = Vec::new();
+
+        // Ethernet header: destination, source, ethertype (IPv4).
+        data.push_all(&[0x01, 0x02, 0x03, 0x04, 0x05, 0x06]);
+        data.push_all(&[0x11, 0x12, 0x13, 0x14, 0x15, 0x16]);
+        data.push_all(&[0x08, 0x00]);
+
+        // IPv4 header: version/IHL, ..., source 10.0.0.1, destination
+        // 10.0.0.2, protocol TCP, checksum left zeroed for now.
+        data.push_all(&[0x45, 0x00, 0x00, 0x28]);
+        data.push_all(&[0x00, 0x00, 0x00, 0x00]);
+        data.push_all(&[0x40, IP_PROTO_TCP, 0x00, 0x00]);
+        data.push_all(&[10, 0, 0, 1]);
+        data.push_all(&[10, 0, 0, 2]);
+
+        let checksum = internet_checksum(&data[14..34]);
+        data[24] = (checksum >> 8) as u8;
+        data[25] = (checksum & 0xFF) as u8;
+
+        // TCP header: source port 1234, destination port 80, data offset
+        // of 5 words, no options or payload.
+        data.push_all(&[0x04, 0xD2]);
+        data.push_all(&[0x00, 0x50]);
+        data.push_all(&[0x00, 0x00, 0x00, 0x00]);
+        data.push_all(&[0x00, 0x00, 0x00, 0x00]);
+        data.push_all(&[0x50, 0x00, 0x00, 0x00]);
+        data.push_all(&[0x00, 0x00, 0x00, 0x00]);
+
+        data
+    }
+
+    #[test]
+    fn dissects_an_ethernet_ipv4_tcp_frame() {
+        let data = ethernet_ipv4_tcp_frame();
+        let frame = Frame::dissect(&data, LINKTYPE_ETHERNET);
+
+        let ethernet = frame.ethernet.expect("expected an Ethernet header");
+        assert_eq!(ethernet.destination, [0x01, 0x02, 0x03, 0x04, 0x05, 0x06]);
+        assert_eq!(ethernet.source, [0x11, 0x12, 0x13, 0x14, 0x15, 0x16]);
+        assert_eq!(ethernet.ethertype, ETHERTYPE_IPV4);
+
+        match frame.network {
+            Some(NetworkPacket::Ipv4(ref ipv4)) => {
+                assert_eq!(ipv4.version, 4);
+                assert_eq!(ipv4.protocol, IP_PROTO_TCP);
+                assert!(ipv4.checksum_valid);
+                assert_eq!(ipv4.source, IpAddr::Ipv4Addr(10, 0, 0, 1));
+                assert_eq!(ipv4.destination, IpAddr::Ipv4Addr(10, 0, 0, 2));
+            }
+            _ => panic!("expected an IPv4 packet")
+        }
+
+        match frame.transport {
+            Some(TransportSegment::Tcp(ref tcp)) => {
+                assert_eq!(tcp.source_port, 1234);
+                assert_eq!(tcp.destination_port, 80);
+                assert_eq!(tcp.payload.len(), 0);
+            }
+            _ => panic!("expected a TCP segment")
+        }
+    }
+
+    #[test]
+    fn dissects_nothing_for_an_unrecognized_link_type() {
+        let data = ethernet_ipv4_tcp_frame();
+        let frame = Frame::dissect(&data, 0xFFFF);
+
+        assert!(frame.ethernet.is_none());
+        assert!(frame.network.is_none());
+        assert!(frame.transport.is_none());
+    }
+}
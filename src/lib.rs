@@ -1,11 +1,13 @@
 #![feature(slicing_syntax, globs)]
 
+pub mod dissect;
+
 use std::error::FromError;
 
 use std::str::Utf8Error;
 use std::string::FromUtf8Error;
 
-use std::io::{Reader, BufReader, IoError};
+use std::io::{Reader, Writer, BufReader, IoError, EndOfFile, InvalidInput};
 use std::io::net::ip::IpAddr;
 
 #[derive(Show)]
@@ -17,6 +19,7 @@ pub enum Error {
 #[derive(Show, Copy)]
 pub enum FormatError {
     UnknownOption,
+    UnknownBlockType(u32),
     Utf8Error(Utf8Error)
 }
 
@@ -38,15 +41,217 @@ impl FromError<FormatError> for Error {
     }
 }
 
-enum Block {
+#[derive(Show)]
+pub enum Block {
     SectionHeader(SectionHeaderBlock),
     InterfaceDescription(InterfaceDescriptionBlock),
+    SimplePacket(SimplePacketBlock),
+    NameResolution(NameResolutionBlock),
     InterfaceStatistics(InterfaceStatisticsBlock),
     EnhancedPacket(EnhancedPacketBlock)
 }
 
+/// Block type codes as they appear on the wire.
+mod block_type {
+    pub const SECTION_HEADER: u32 = 0x0A0D0D0A;
+    pub const INTERFACE_DESCRIPTION: u32 = 0x00000001;
+    pub const SIMPLE_PACKET: u32 = 0x00000003;
+    pub const NAME_RESOLUTION: u32 = 0x00000004;
+    pub const INTERFACE_STATISTICS: u32 = 0x00000005;
+    pub const ENHANCED_PACKET: u32 = 0x00000006;
+}
+
+/// Byte order of a section, detected from its Section Header Block's magic
+/// number. Scoped per section: a new Section Header Block may switch it.
+#[derive(Show, Copy, Clone, PartialEq, Eq)]
+pub enum Endianness {
+    Little,
+    Big
+}
+
+impl Endianness {
+    fn read_u16<R: Reader>(&self, r: &mut R) -> Result<u16, IoError> {
+        match *self {
+            Endianness::Little => r.read_le_u16(),
+            Endianness::Big => r.read_be_u16()
+        }
+    }
+
+    fn read_u32<R: Reader>(&self, r: &mut R) -> Result<u32, IoError> {
+        match *self {
+            Endianness::Little => r.read_le_u32(),
+            Endianness::Big => r.read_be_u32()
+        }
+    }
+
+    fn read_u64<R: Reader>(&self, r: &mut R) -> Result<u64, IoError> {
+        match *self {
+            Endianness::Little => r.read_le_u64(),
+            Endianness::Big => r.read_be_u64()
+        }
+    }
+
+    fn read_uint_n<R: Reader>(&self, r: &mut R, nbytes: uint) -> Result<u64, IoError> {
+        match *self {
+            Endianness::Little => r.read_le_uint_n(nbytes),
+            Endianness::Big => r.read_be_uint_n(nbytes)
+        }
+    }
+
+    fn write_u16<W: Writer>(&self, w: &mut W, value: u16) -> Result<(), IoError> {
+        match *self {
+            Endianness::Little => w.write_le_u16(value),
+            Endianness::Big => w.write_be_u16(value)
+        }
+    }
+
+    fn write_u32<W: Writer>(&self, w: &mut W, value: u32) -> Result<(), IoError> {
+        match *self {
+            Endianness::Little => w.write_le_u32(value),
+            Endianness::Big => w.write_be_u32(value)
+        }
+    }
+
+    fn write_u64<W: Writer>(&self, w: &mut W, value: u64) -> Result<(), IoError> {
+        match *self {
+            Endianness::Little => w.write_le_u64(value),
+            Endianness::Big => w.write_be_u64(value)
+        }
+    }
+
+    fn write_uint_n<W: Writer>(&self, w: &mut W, value: u64, nbytes: uint) -> Result<(), IoError> {
+        match *self {
+            Endianness::Little => w.write_le_uint_n(value, nbytes),
+            Endianness::Big => w.write_be_uint_n(value, nbytes)
+        }
+    }
+}
+
+/// Pulls `Block`s one at a time out of any `Reader`.
+///
+/// Unlike the individual `*Block::read` methods, which each operate on an
+/// already-framed `BufReader` over a single block's payload, `PcapNgReader`
+/// owns the underlying stream and handles the `block_type`/`total_len`
+/// framing itself, so it works directly on sockets, pipes, or anything else
+/// that only implements `Reader`.
+pub struct PcapNgReader<R> {
+    reader: R,
+
+    /// Byte order of the section currently being read. Reset every time a
+    /// new Section Header Block is encountered.
+    endianness: Endianness,
+
+    /// Interface description blocks seen so far, indexed by `interface_id`.
+    interfaces: Vec<InterfaceDescriptionBlock>
+}
+
+impl<R: Reader> PcapNgReader<R> {
+    pub fn new(reader: R) -> PcapNgReader<R> {
+        PcapNgReader {
+            reader: reader,
+            endianness: Endianness::Little,
+            interfaces: Vec::new()
+        }
+    }
+
+    /// Reads and returns the next block in the stream, or `None` once the
+    /// stream is exhausted.
+    pub fn next_block(&mut self) -> Result<Option<Block>, Error> {
+        let (block_type, data, endianness) = match read_block(&mut self.reader, self.endianness) {
+            Ok(triple) => triple,
+            Err(IoError { kind: EndOfFile, .. }) => return Ok(None),
+            Err(err) => return Err(FromError::from_error(err))
+        };
+
+        self.endianness = endianness;
+
+        let mut r = BufReader::new(&*data);
+
+        let block = match block_type {
+            block_type::SECTION_HEADER => {
+                // interface_id is scoped per-section; a new section starts
+                // its interfaces back at 0.
+                self.interfaces.clear();
+                Block::SectionHeader(try!(SectionHeaderBlock::read(&mut r, endianness)))
+            }
+
+            block_type::INTERFACE_DESCRIPTION => {
+                let idb = try!(InterfaceDescriptionBlock::read(&mut r, endianness));
+                self.interfaces.push(idb.clone());
+                Block::InterfaceDescription(idb)
+            }
+
+            block_type::SIMPLE_PACKET => {
+                // A Simple Packet Block carries no interface id of its own;
+                // it belongs to the section's (only) interface.
+                let snap_len = self.interfaces.last().map_or(0, |idb| idb.snap_len);
+                Block::SimplePacket(try!(SimplePacketBlock::read(&mut r, endianness, snap_len)))
+            }
+
+            block_type::NAME_RESOLUTION =>
+                Block::NameResolution(try!(NameResolutionBlock::read(&mut r, endianness))),
+
+            block_type::INTERFACE_STATISTICS =>
+                Block::InterfaceStatistics(try!(InterfaceStatisticsBlock::read(&mut r, endianness))),
+
+            block_type::ENHANCED_PACKET =>
+                Block::EnhancedPacket(try!(EnhancedPacketBlock::read(&mut r, endianness))),
+
+            other => return Err(FromError::from_error(FormatError::UnknownBlockType(other)))
+        };
+
+        Ok(Some(block))
+    }
+
+    /// Interface description blocks seen so far, indexed by `interface_id`.
+    /// Used to resolve the interface an `EnhancedPacketBlock` was captured on.
+    pub fn interfaces(&self) -> &[InterfaceDescriptionBlock] {
+        self.interfaces.as_slice()
+    }
+}
+
+/// Writes `Block`s one at a time to any `Writer`, the complement to
+/// `PcapNgReader`. Each block is serialized with its own `write()` method;
+/// this just forwards to whichever one matches the block in hand.
+pub struct PcapNgWriter<W> {
+    writer: W,
+
+    /// Byte order of the section currently being written. Updated to match
+    /// every `SectionHeaderBlock` written, so later blocks in that section
+    /// are encoded the same way `PcapNgReader` decoded them.
+    endianness: Endianness
+}
+
+impl<W: Writer> PcapNgWriter<W> {
+    pub fn new(writer: W) -> PcapNgWriter<W> {
+        PcapNgWriter { writer: writer, endianness: Endianness::Little }
+    }
+
+    pub fn write_block(&mut self, block: &Block) -> Result<(), IoError> {
+        if let Block::SectionHeader(ref b) = *block {
+            self.endianness = b.endianness;
+        }
+
+        let endianness = self.endianness;
+
+        match *block {
+            Block::SectionHeader(ref b) => b.write(&mut self.writer, endianness),
+            Block::InterfaceDescription(ref b) => b.write(&mut self.writer, endianness),
+            Block::SimplePacket(ref b) => b.write(&mut self.writer, endianness),
+            Block::NameResolution(ref b) => b.write(&mut self.writer, endianness),
+            Block::InterfaceStatistics(ref b) => b.write(&mut self.writer, endianness),
+            Block::EnhancedPacket(ref b) => b.write(&mut self.writer, endianness)
+        }
+    }
+}
+
 #[derive(Show)]
 pub struct SectionHeaderBlock {
+    /// Byte order this section was read in, detected from `magic` by
+    /// `read_block`. Carried on the block so a `PcapNgWriter` can write the
+    /// section back out in the same order it came in.
+    pub endianness: Endianness,
+
     /// Magic number equal to 0x1A2B3C4D. This field can be used to detect
     /// sections saved on systems with different endianness.
     pub magic: u32,
@@ -77,8 +282,8 @@ pub enum SectionHeaderOption {
     UserApplication(String)
 }
 
-/// Block type 1 
-#[derive(Show)]
+/// Block type 1
+#[derive(Show, Clone)]
 pub struct InterfaceDescriptionBlock {
     /// Link layer type of the interface
     pub link_type: u16,
@@ -89,7 +294,7 @@ pub struct InterfaceDescriptionBlock {
     pub options: Vec<InterfaceDescriptionOption>
 }
 
-#[derive(Show)]
+#[derive(Show, Clone)]
 pub enum InterfaceDescriptionOption {
     /// Comment associated with the current block
     Comment(String),
@@ -148,6 +353,40 @@ pub enum InterfaceDescriptionOption {
     TsOffset(u64)
 }
 
+/// Block type 3
+#[derive(Show)]
+pub struct SimplePacketBlock {
+    /// Actual length of the packet when it was transmitted on the network.
+    pub len: u32,
+
+    /// Captured packet data. Unlike `EnhancedPacketBlock`, there is no
+    /// separate captured-length field; this is derived from the block's
+    /// total length, capped by the section's snap length.
+    pub data: Vec<u8>
+}
+
+/// Block type 4
+#[derive(Show)]
+pub struct NameResolutionBlock {
+    pub records: Vec<NameResolutionRecord>,
+    pub options: Vec<NameResolutionOption>
+}
+
+#[derive(Show)]
+pub enum NameResolutionRecord {
+    /// IPv4 address and the DNS names that resolve to it
+    Ipv4(u32, Vec<String>),
+
+    /// IPv6 address and the DNS names that resolve to it
+    Ipv6(IpAddr, Vec<String>)
+}
+
+#[derive(Show)]
+pub enum NameResolutionOption {
+    /// Comment associated with the current block
+    Comment(String)
+}
+
 /// Block type 5
 #[derive(Show)]
 pub struct InterfaceStatisticsBlock {
@@ -204,11 +443,55 @@ pub struct EnhancedPacketBlock {
 #[derive(Show)]
 pub enum EnhancedPacketBlockOption {
     Comment(String),
-    Flags(u32),
-    Hash,
+
+    /// Link-layer-independent flags for the packet
+    Flags(PacketFlags),
+
+    /// Hash of the packet data: the algorithm used and the digest.
+    ///
+    /// Algorithm 0 = 2's complement, 1 = XOR, 2 = CRC32, 3 = MD5, 4 = SHA1.
+    Hash(u8, Vec<u8>),
 
     /// Number of packets lost between the last packet
-    DropCount(u64)
+    DropCount(u64),
+
+    /// Sequential number assigned to this packet when it was captured
+    PacketId(u64),
+
+    /// Verdict assigned to the packet: the verdict type and opaque data
+    Verdict(u8, Vec<u8>)
+}
+
+/// The 32-bit flags word attached to an `EnhancedPacketBlock` via option
+/// code 2, unpacked into its constituent fields.
+#[derive(Show, Copy, Clone)]
+pub struct PacketFlags {
+    pub direction: Direction,
+    pub reception_type: ReceptionType,
+
+    /// Length of the frame check sequence in bits, or `None` if not
+    /// specified for this packet.
+    pub fcs_len: Option<u8>,
+
+    /// Link-layer-dependent error bits (e.g. CRC error, packet too long or
+    /// too short), taken from the flags word's high 16 bits.
+    pub link_layer_errors: u16
+}
+
+#[derive(Show, Copy, Clone, PartialEq, Eq)]
+pub enum Direction {
+    Unspecified,
+    Inbound,
+    Outbound
+}
+
+#[derive(Show, Copy, Clone, PartialEq, Eq)]
+pub enum ReceptionType {
+    Unspecified,
+    Unicast,
+    Multicast,
+    Broadcast,
+    Promiscuous
 }
 
 #[inline(always)]
@@ -216,23 +499,95 @@ fn dword_aligned(n: uint) -> uint {
     (n + 3) & !3
 }
 
-pub fn read_block(r: &mut Reader) -> Result<(u32, Vec<u8>), IoError> {
-    let block_type = try!(r.read_le_u32());
+/// Reads the generic `block_type`/`total_len` framing and returns the
+/// block's inner data, along with the section's current `Endianness`.
+///
+/// A Section Header Block may open a new section in a different byte order
+/// than the one passed in, so `total_len` can't be trusted until the magic
+/// number that immediately follows it has been inspected; in that case the
+/// raw bytes are re-interpreted once the true order is known and the
+/// detected `Endianness` is handed back to the caller to use from here on.
+pub fn read_block(r: &mut Reader, endianness: Endianness) -> Result<(u32, Vec<u8>, Endianness), IoError> {
+    // block_type::SECTION_HEADER's bytes are a palindrome, so reading it
+    // with the old section's endianness still recovers the right value
+    // even when this block is about to open a new section in the other
+    // byte order; every other block type is read in the order it's
+    // actually on the wire in, i.e. the current section's.
+    let block_type = try!(endianness.read_u32(r));
+
+    if block_type == block_type::SECTION_HEADER {
+        let total_len_le = try!(r.read_le_u32());
+        let magic = try!(r.read_le_u32());
 
-    let total_len = try!(r.read_le_u32());
-    let data_len = total_len - 12; // 12 = type + 2*length
+        let (endianness, total_len) = match magic {
+            0x1A2B3C4D => (Endianness::Little, total_len_le),
+            0x4D3C2B1A => (Endianness::Big, total_len_le.swap_bytes()),
+            _ => return Err(IoError {
+                kind: InvalidInput,
+                desc: "bad pcapng magic number",
+                detail: None
+            })
+        };
+
+        if total_len < 16 { // 12 = type + 2*length, plus the 4-byte magic
+            return Err(IoError {
+                kind: InvalidInput,
+                desc: "truncated pcapng section header block",
+                detail: None
+            });
+        }
+
+        let data_len = total_len - 12; // 12 = type + 2*length
+        let magic_bytes = match endianness {
+            Endianness::Little => [0x4D, 0x3C, 0x2B, 0x1A],
+            Endianness::Big => [0x1A, 0x2B, 0x3C, 0x4D]
+        };
+
+        let mut rest = try!(r.read_exact(dword_aligned(data_len as uint) - 4));
+
+        let mut data = Vec::with_capacity(data_len as uint);
+        data.push_all(&magic_bytes);
+        data.push_all(rest.as_slice());
+        data.truncate(data_len as uint);
+
+        let trailing_raw = try!(r.read_le_u32());
+        let trailing = if endianness == Endianness::Big { trailing_raw.swap_bytes() } else { trailing_raw };
+        assert!(total_len == trailing);
+
+        Ok((block_type, data, endianness))
+    } else {
+        let total_len = try!(endianness.read_u32(r));
+
+        if total_len < 12 { // 12 = type + 2*length
+            return Err(IoError {
+                kind: InvalidInput,
+                desc: "truncated pcapng block",
+                detail: None
+            });
+        }
+
+        let data_len = total_len - 12;
 
-    let mut data = try!(r.read_exact(dword_aligned(data_len as uint)));
-    data.truncate(data_len as uint);
+        let mut data = try!(r.read_exact(dword_aligned(data_len as uint)));
+        data.truncate(data_len as uint);
 
-    assert!(total_len == try!(r.read_le_u32()));
+        let trailing = try!(endianness.read_u32(r));
 
-    Ok((block_type, data))
+        if total_len != trailing {
+            return Err(IoError {
+                kind: InvalidInput,
+                desc: "pcapng block length mismatch",
+                detail: None
+            });
+        }
+
+        Ok((block_type, data, endianness))
+    }
 }
 
-fn read_option(r: &mut Reader) -> Result<(u16, Vec<u8>), IoError> {
-    let code = try!(r.read_le_u16());
-    let len = try!(r.read_le_u16()) as uint;
+fn read_option(r: &mut Reader, endianness: Endianness) -> Result<(u16, Vec<u8>), IoError> {
+    let code = try!(endianness.read_u16(r));
+    let len = try!(endianness.read_u16(r)) as uint;
 
     let mut data = try!(r.read_exact(dword_aligned(len)));
     data.truncate(len as uint);
@@ -240,15 +595,178 @@ fn read_option(r: &mut Reader) -> Result<(u16, Vec<u8>), IoError> {
     Ok((code, data))
 }
 
+/// Serializes a block's generic `block_type`/`total_len` framing around an
+/// already-encoded `payload`, including the padding and trailing length
+/// copy that `read_block` expects to find. `endianness` is the byte order
+/// of the section this block belongs to.
+fn encode_block(block_type: u32, payload: &[u8], endianness: Endianness) -> Vec<u8> {
+    let aligned_len = dword_aligned(payload.len());
+    let total_len = (12 + aligned_len) as u32; // 12 = type + 2*length
+
+    let mut buf = Vec::with_capacity(total_len as uint);
+    endianness.write_u32(&mut buf, block_type).unwrap();
+    endianness.write_u32(&mut buf, total_len).unwrap();
+    buf.push_all(payload);
+
+    for _ in range(0, aligned_len - payload.len()) {
+        buf.push(0u8);
+    }
+
+    endianness.write_u32(&mut buf, total_len).unwrap();
+    buf
+}
+
+/// Serializes a single option's code/length/data/padding, the counterpart to
+/// `read_option`.
+fn encode_option(code: u16, data: &[u8], endianness: Endianness) -> Vec<u8> {
+    let mut buf = Vec::with_capacity(4 + dword_aligned(data.len()));
+    endianness.write_u16(&mut buf, code).unwrap();
+    endianness.write_u16(&mut buf, data.len() as u16).unwrap();
+    buf.push_all(data);
+
+    for _ in range(0, dword_aligned(data.len()) - data.len()) {
+        buf.push(0u8);
+    }
+
+    buf
+}
+
+/// The `opt_endofopt` record (code 0, length 0) that terminates every
+/// option list.
+fn encode_end_of_opt(endianness: Endianness) -> Vec<u8> {
+    encode_option(0, &[], endianness)
+}
+
+/// Serializes a `u64` value, the counterpart to `Endianness::read_u64`.
+fn encode_u64(value: u64, endianness: Endianness) -> Vec<u8> {
+    let mut buf = Vec::new();
+    endianness.write_u64(&mut buf, value).unwrap();
+    buf
+}
+
+/// Serializes an `IpAddr` to its wire representation (network byte order),
+/// the counterpart of the address parsing done when reading options.
+fn encode_ipaddr(addr: &IpAddr) -> Vec<u8> {
+    let mut buf = Vec::new();
+
+    match *addr {
+        IpAddr::Ipv4Addr(a, b, c, d) => {
+            buf.push(a);
+            buf.push(b);
+            buf.push(c);
+            buf.push(d);
+        }
+
+        IpAddr::Ipv6Addr(a, b, c, d, e, f, g, h) => {
+            for &part in [a, b, c, d, e, f, g, h].iter() {
+                buf.write_be_u16(part).unwrap();
+            }
+        }
+    }
+
+    buf
+}
+
+/// Reads a 16-byte IPv6 address in network byte order out of a Name
+/// Resolution record.
+fn read_ipv6addr(d: &mut &[u8]) -> Result<IpAddr, IoError> {
+    Ok(IpAddr::Ipv6Addr(
+        try!(d.read_be_u16()), try!(d.read_be_u16()),
+        try!(d.read_be_u16()), try!(d.read_be_u16()),
+        try!(d.read_be_u16()), try!(d.read_be_u16()),
+        try!(d.read_be_u16()), try!(d.read_be_u16())))
+}
+
+/// Splits a Name Resolution record's trailing bytes into the one or more
+/// NUL-terminated DNS names it holds.
+fn split_nul_names(bytes: &[u8]) -> Result<Vec<String>, Error> {
+    let mut names = Vec::new();
+
+    for chunk in bytes.split(|&b| b == 0) {
+        if !chunk.is_empty() {
+            names.push(try!(String::from_utf8(chunk.to_vec())));
+        }
+    }
+
+    Ok(names)
+}
+
+/// Serializes a Name Resolution record's DNS names back into NUL-terminated
+/// bytes, the counterpart of `split_nul_names`.
+fn encode_nul_names(names: &[String]) -> Vec<u8> {
+    let mut buf = Vec::new();
+
+    for name in names.iter() {
+        buf.push_all(name.as_bytes());
+        buf.push(0u8);
+    }
+
+    buf
+}
+
+/// Unpacks an `EnhancedPacketBlock` flags word (option code 2) into its
+/// constituent fields: bits 0-1 direction, bits 2-4 reception type, bits
+/// 5-8 FCS length, and the link-layer-dependent error bits in the high
+/// 16 bits.
+fn parse_flags(flags: u32) -> PacketFlags {
+    let direction = match flags & 0x3 {
+        1 => Direction::Inbound,
+        2 => Direction::Outbound,
+        _ => Direction::Unspecified
+    };
+
+    let reception_type = match (flags >> 2) & 0x7 {
+        1 => ReceptionType::Unicast,
+        2 => ReceptionType::Multicast,
+        3 => ReceptionType::Broadcast,
+        4 => ReceptionType::Promiscuous,
+        _ => ReceptionType::Unspecified
+    };
+
+    let fcs_nibble = ((flags >> 5) & 0xF) as u8;
+
+    PacketFlags {
+        direction: direction,
+        reception_type: reception_type,
+        fcs_len: if fcs_nibble == 0 { None } else { Some(fcs_nibble) },
+        link_layer_errors: (flags >> 16) as u16
+    }
+}
+
+/// Packs a `PacketFlags` back into its 32-bit wire representation, the
+/// counterpart of `parse_flags`.
+fn encode_flags(flags: &PacketFlags) -> u32 {
+    let direction = match flags.direction {
+        Direction::Unspecified => 0u32,
+        Direction::Inbound => 1,
+        Direction::Outbound => 2
+    };
+
+    let reception_type = match flags.reception_type {
+        ReceptionType::Unspecified => 0u32,
+        ReceptionType::Unicast => 1,
+        ReceptionType::Multicast => 2,
+        ReceptionType::Broadcast => 3,
+        ReceptionType::Promiscuous => 4
+    };
+
+    let fcs_len = match flags.fcs_len {
+        None => 0u32,
+        Some(n) => n as u32
+    };
+
+    direction | (reception_type << 2) | (fcs_len << 5) | ((flags.link_layer_errors as u32) << 16)
+}
+
 impl SectionHeaderBlock {
-    pub fn read(r: &mut BufReader) -> Result<SectionHeaderBlock, Error> {
-        let magic = try!(r.read_le_u32());
-    
+    pub fn read(r: &mut BufReader, endianness: Endianness) -> Result<SectionHeaderBlock, Error> {
+        let magic = try!(endianness.read_u32(r));
+
         assert!(magic == 0x1A2B3C4D, "unsupported endianness");
 
-        let major_version = try!(r.read_le_u16());
-        let minor_version = try!(r.read_le_u16());
-        let section_length = try!(r.read_le_u64());
+        let major_version = try!(endianness.read_u16(r));
+        let minor_version = try!(endianness.read_u16(r));
+        let section_length = try!(endianness.read_u64(r));
 
         let mut options = Vec::new();
 
@@ -256,7 +774,7 @@ impl SectionHeaderBlock {
             loop {
                 use SectionHeaderOption::*;
 
-                let (code, data) = try!(read_option(r));
+                let (code, data) = try!(read_option(r, endianness));
 
                 let opt = match code {
                     0 => break,
@@ -281,6 +799,7 @@ impl SectionHeaderBlock {
         }
 
         Ok(SectionHeaderBlock {
+            endianness: endianness,
             magic: magic,
             major_version: major_version,
             minor_version: minor_version,
@@ -289,20 +808,52 @@ impl SectionHeaderBlock {
             options: options
         })
     }
+
+    /// Serializes this block to bytes, including the generic block framing,
+    /// ready to append to a file. `endianness` should normally be
+    /// `self.endianness`, to round-trip the section's original byte order.
+    pub fn to_bytes(&self, endianness: Endianness) -> Vec<u8> {
+        use SectionHeaderOption::*;
+
+        let mut payload = Vec::new();
+        endianness.write_u32(&mut payload, self.magic).unwrap();
+        endianness.write_u16(&mut payload, self.major_version).unwrap();
+        endianness.write_u16(&mut payload, self.minor_version).unwrap();
+        endianness.write_u64(&mut payload, self.section_length).unwrap();
+
+        for opt in self.options.iter() {
+            let (code, data) = match *opt {
+                Comment(ref s) => (1u16, s.clone().into_bytes()),
+                Hardware(ref s) => (2u16, s.clone().into_bytes()),
+                OS(ref s) => (3u16, s.clone().into_bytes()),
+                UserApplication(ref s) => (4u16, s.clone().into_bytes())
+            };
+
+            payload.push_all(&*encode_option(code, &*data, endianness));
+        }
+        payload.push_all(&*encode_end_of_opt(endianness));
+
+        encode_block(block_type::SECTION_HEADER, &*payload, endianness)
+    }
+
+    /// Writes this block, including the generic block framing, to `w`.
+    pub fn write<W: Writer>(&self, w: &mut W, endianness: Endianness) -> Result<(), IoError> {
+        w.write(&*self.to_bytes(endianness))
+    }
 }
 
 impl InterfaceDescriptionBlock {
-    pub fn read(r: &mut BufReader) -> Result<InterfaceDescriptionBlock, Error> {
-        let link_type = try!(r.read_le_u16());
-        try!(r.read_le_u16()); // reserved
-        let snap_len = try!(r.read_le_u32());
+    pub fn read(r: &mut BufReader, endianness: Endianness) -> Result<InterfaceDescriptionBlock, Error> {
+        let link_type = try!(endianness.read_u16(r));
+        try!(endianness.read_u16(r)); // reserved
+        let snap_len = try!(endianness.read_u32(r));
 
         let mut options = Vec::new();
 
         if !r.eof() {
             loop {
                 use InterfaceDescriptionOption::*;
-                let (code, data) = try!(read_option(r));
+                let (code, data) = try!(read_option(r, endianness));
 
                 let mut d = &*data;
 
@@ -320,15 +871,36 @@ impl InterfaceDescriptionBlock {
                         }
                     }
 
+                    // Interface addresses (codes 4-7) are raw hardware/
+                    // network bytes in a fixed wire order, independent of
+                    // the section's Endianness - just like Ipv6Addr below.
                     4 => {
-                        let ip = try!(d.read_le_u32());
-                        let mask = try!(d.read_le_u32());
+                        let ip = try!(d.read_be_u32());
+                        let mask = try!(d.read_be_u32());
 
                         Ipv4Addr(ip, mask)
                     }
 
-                    6 => MacAddr(try!(d.read_le_uint_n(6))),
+                    5 => {
+                        let addr = try!(read_ipv6addr(&mut d));
+                        let prefix_len = try!(d.read_byte());
+
+                        Ipv6Addr(addr, prefix_len)
+                    }
+
+                    6 => MacAddr(try!(d.read_be_uint_n(6))),
+                    7 => EuiAddr(try!(d.read_be_u64())),
+                    8 => Speed(try!(endianness.read_u64(&mut d))),
                     9 => TsResolution(try!(d.read_byte())),
+                    10 => Timezone(try!(endianness.read_u32(&mut d))),
+
+                    11 => {
+                        let kind = try!(d.read_byte());
+                        Filter(kind, d.to_vec())
+                    }
+
+                    13 => FcsLen(try!(d.read_byte())),
+                    14 => TsOffset(try!(endianness.read_u64(&mut d))),
 
                     _ => return Err(FromError::from_error(FormatError::UnknownOption))
                 };
@@ -343,14 +915,217 @@ impl InterfaceDescriptionBlock {
             options: options
         })
     }
+
+    /// Serializes this block to bytes, including the generic block framing,
+    /// ready to append to a file.
+    pub fn to_bytes(&self, endianness: Endianness) -> Vec<u8> {
+        use InterfaceDescriptionOption::*;
+
+        let mut payload = Vec::new();
+        endianness.write_u16(&mut payload, self.link_type).unwrap();
+        endianness.write_u16(&mut payload, 0).unwrap(); // reserved
+        endianness.write_u32(&mut payload, self.snap_len).unwrap();
+
+        for opt in self.options.iter() {
+            let (code, data) = match *opt {
+                Comment(ref s) => (1u16, s.clone().into_bytes()),
+                Name(ref s) => (2u16, s.clone().into_bytes()),
+                Description(ref s) => (3u16, s.clone().into_bytes()),
+
+                Ipv4Addr(ip, mask) => {
+                    let mut data = Vec::new();
+                    data.write_be_u32(ip).unwrap();
+                    data.write_be_u32(mask).unwrap();
+                    (4u16, data)
+                }
+
+                Ipv6Addr(ref addr, prefix_len) => {
+                    let mut data = encode_ipaddr(addr);
+                    data.push(prefix_len);
+                    (5u16, data)
+                }
+
+                MacAddr(mac) => {
+                    let mut data = Vec::new();
+                    data.write_be_uint_n(mac, 6).unwrap();
+                    (6u16, data)
+                }
+
+                EuiAddr(eui) => {
+                    let mut data = Vec::new();
+                    data.write_be_u64(eui).unwrap();
+                    (7u16, data)
+                }
+
+                Speed(speed) => (8u16, encode_u64(speed, endianness)),
+                TsResolution(res) => (9u16, vec![res]),
+
+                Timezone(tz) => {
+                    let mut data = Vec::new();
+                    endianness.write_u32(&mut data, tz).unwrap();
+                    (10u16, data)
+                }
+
+                Filter(kind, ref bytecode) => {
+                    let mut data = vec![kind];
+                    data.push_all(&**bytecode);
+                    (11u16, data)
+                }
+
+                OS(ref s) => (12u16, s.clone().into_bytes()),
+                FcsLen(len) => (13u16, vec![len]),
+                TsOffset(offset) => (14u16, encode_u64(offset, endianness))
+            };
+
+            payload.push_all(&*encode_option(code, &*data, endianness));
+        }
+        payload.push_all(&*encode_end_of_opt(endianness));
+
+        encode_block(block_type::INTERFACE_DESCRIPTION, &*payload, endianness)
+    }
+
+    /// Writes this block, including the generic block framing, to `w`.
+    pub fn write<W: Writer>(&self, w: &mut W, endianness: Endianness) -> Result<(), IoError> {
+        w.write(&*self.to_bytes(endianness))
+    }
+}
+
+impl SimplePacketBlock {
+    /// `snap_len` is the snap length of the section's (only) interface, used
+    /// to derive the captured length since this block has no field of its
+    /// own for it; 0 means unlimited.
+    pub fn read(r: &mut BufReader, endianness: Endianness, snap_len: u32) -> Result<SimplePacketBlock, Error> {
+        let len = try!(endianness.read_u32(r));
+        let mut data = try!(r.read_to_end());
+
+        if snap_len != 0 && data.len() > snap_len as uint {
+            data.truncate(snap_len as uint);
+        }
+
+        Ok(SimplePacketBlock {
+            len: len,
+            data: data
+        })
+    }
+
+    /// Serializes this block to bytes, including the generic block framing,
+    /// ready to append to a file.
+    pub fn to_bytes(&self, endianness: Endianness) -> Vec<u8> {
+        let mut payload = Vec::new();
+        endianness.write_u32(&mut payload, self.len).unwrap();
+        payload.push_all(&*self.data);
+
+        encode_block(block_type::SIMPLE_PACKET, &*payload, endianness)
+    }
+
+    /// Writes this block, including the generic block framing, to `w`.
+    pub fn write<W: Writer>(&self, w: &mut W, endianness: Endianness) -> Result<(), IoError> {
+        w.write(&*self.to_bytes(endianness))
+    }
+}
+
+impl NameResolutionBlock {
+    pub fn read(r: &mut BufReader, endianness: Endianness) -> Result<NameResolutionBlock, Error> {
+        let mut records = Vec::new();
+
+        loop {
+            let (record_type, data) = try!(read_option(r, endianness));
+
+            match record_type {
+                0 => break,
+
+                1 => {
+                    let mut d = &*data;
+                    let address = try!(d.read_be_u32());
+                    let names = try!(split_nul_names(d));
+
+                    records.push(NameResolutionRecord::Ipv4(address, names));
+                }
+
+                2 => {
+                    let mut d = &*data;
+                    let address = try!(read_ipv6addr(&mut d));
+                    let names = try!(split_nul_names(d));
+
+                    records.push(NameResolutionRecord::Ipv6(address, names));
+                }
+
+                _ => return Err(FromError::from_error(FormatError::UnknownOption))
+            }
+        }
+
+        let mut options = Vec::new();
+
+        if !r.eof() {
+            loop {
+                use NameResolutionOption::*;
+
+                let (code, data) = try!(read_option(r, endianness));
+
+                let opt = match code {
+                    0 => break,
+                    1 => Comment(try!(String::from_utf8(data))),
+                    _ => return Err(FromError::from_error(FormatError::UnknownOption))
+                };
+
+                options.push(opt);
+            }
+        }
+
+        Ok(NameResolutionBlock {
+            records: records,
+            options: options
+        })
+    }
+
+    /// Serializes this block to bytes, including the generic block framing,
+    /// ready to append to a file.
+    pub fn to_bytes(&self, endianness: Endianness) -> Vec<u8> {
+        use NameResolutionOption::*;
+
+        let mut payload = Vec::new();
+
+        for record in self.records.iter() {
+            let (record_type, mut data, names) = match *record {
+                NameResolutionRecord::Ipv4(address, ref names) => {
+                    let mut data = Vec::new();
+                    data.write_be_u32(address).unwrap();
+                    (1u16, data, names)
+                }
+
+                NameResolutionRecord::Ipv6(ref address, ref names) =>
+                    (2u16, encode_ipaddr(address), names)
+            };
+
+            data.push_all(&*encode_nul_names(&**names));
+            payload.push_all(&*encode_option(record_type, &*data, endianness));
+        }
+        payload.push_all(&*encode_end_of_opt(endianness)); // end of records
+
+        for opt in self.options.iter() {
+            let (code, data) = match *opt {
+                Comment(ref s) => (1u16, s.clone().into_bytes())
+            };
+
+            payload.push_all(&*encode_option(code, &*data, endianness));
+        }
+        payload.push_all(&*encode_end_of_opt(endianness));
+
+        encode_block(block_type::NAME_RESOLUTION, &*payload, endianness)
+    }
+
+    /// Writes this block, including the generic block framing, to `w`.
+    pub fn write<W: Writer>(&self, w: &mut W, endianness: Endianness) -> Result<(), IoError> {
+        w.write(&*self.to_bytes(endianness))
+    }
 }
 
 impl EnhancedPacketBlock {
-    pub fn read(r: &mut BufReader) -> Result<EnhancedPacketBlock, Error> {
-        let interface_id = try!(r.read_le_u32());
-        let ts = try!(r.read_le_u64());
-        let cap_len = try!(r.read_le_u32());
-        let len = try!(r.read_le_u32());
+    pub fn read(r: &mut BufReader, endianness: Endianness) -> Result<EnhancedPacketBlock, Error> {
+        let interface_id = try!(endianness.read_u32(r));
+        let ts = try!(endianness.read_u64(r));
+        let cap_len = try!(endianness.read_u32(r));
+        let len = try!(endianness.read_u32(r));
 
         let aligned_len = dword_aligned(cap_len as uint);
 
@@ -362,7 +1137,7 @@ impl EnhancedPacketBlock {
         if !r.eof() {
             loop {
                 use EnhancedPacketBlockOption::*;
-                let (code, data) = try!(read_option(r));
+                let (code, data) = try!(read_option(r, endianness));
 
                 let opt = match code {
                     0 => break,
@@ -373,7 +1148,33 @@ impl EnhancedPacketBlock {
                         }
                     }
 
-                    // TODO: rest of the options
+                    2 => {
+                        let mut d = &*data;
+                        Flags(parse_flags(try!(endianness.read_u32(&mut d))))
+                    }
+
+                    3 => {
+                        let mut d = &*data;
+                        let algorithm = try!(d.read_byte());
+                        Hash(algorithm, d.to_vec())
+                    }
+
+                    4 => {
+                        let mut d = &*data;
+                        DropCount(try!(endianness.read_u64(&mut d)))
+                    }
+
+                    5 => {
+                        let mut d = &*data;
+                        PacketId(try!(endianness.read_u64(&mut d)))
+                    }
+
+                    7 => {
+                        let mut d = &*data;
+                        let verdict_type = try!(d.read_byte());
+                        Verdict(verdict_type, d.to_vec())
+                    }
+
                     _ => return Err(FromError::from_error(FormatError::UnknownOption))
                 };
 
@@ -390,19 +1191,184 @@ impl EnhancedPacketBlock {
             options: options
         })
     }
+
+    /// Serializes this block to bytes, including the generic block framing,
+    /// ready to append to a file.
+    pub fn to_bytes(&self, endianness: Endianness) -> Vec<u8> {
+        use EnhancedPacketBlockOption::*;
+
+        let mut payload = Vec::new();
+        endianness.write_u32(&mut payload, self.interface_id).unwrap();
+        endianness.write_u64(&mut payload, self.timestamp).unwrap();
+        endianness.write_u32(&mut payload, self.captured_len).unwrap();
+        endianness.write_u32(&mut payload, self.len).unwrap();
+
+        payload.push_all(&*self.data);
+        for _ in range(0, dword_aligned(self.data.len()) - self.data.len()) {
+            payload.push(0u8);
+        }
+
+        for opt in self.options.iter() {
+            let (code, data) = match *opt {
+                Comment(ref s) => (1u16, s.clone().into_bytes()),
+
+                Flags(ref flags) => {
+                    let mut data = Vec::new();
+                    endianness.write_u32(&mut data, encode_flags(flags)).unwrap();
+                    (2u16, data)
+                }
+
+                Hash(algorithm, ref value) => {
+                    let mut data = vec![algorithm];
+                    data.push_all(&**value);
+                    (3u16, data)
+                }
+
+                DropCount(count) => (4u16, encode_u64(count, endianness)),
+                PacketId(id) => (5u16, encode_u64(id, endianness)),
+
+                Verdict(verdict_type, ref value) => {
+                    let mut data = vec![verdict_type];
+                    data.push_all(&**value);
+                    (7u16, data)
+                }
+            };
+
+            payload.push_all(&*encode_option(code, &*data, endianness));
+        }
+        payload.push_all(&*encode_end_of_opt(endianness));
+
+        encode_block(block_type::ENHANCED_PACKET, &*payload, endianness)
+    }
+
+    /// Writes this block, including the generic block framing, to `w`.
+    pub fn write<W: Writer>(&self, w: &mut W, endianness: Endianness) -> Result<(), IoError> {
+        w.write(&*self.to_bytes(endianness))
+    }
+
+    /// Resolves the raw `timestamp` tick count into wall-clock time, honoring
+    /// the owning interface's `if_tsresol` (default microseconds) and
+    /// `if_tsoffset` (default zero) options, and returns `(seconds, nanos)`
+    /// since the epoch.
+    ///
+    /// Uses integer math throughout - power-of-ten resolutions are applied
+    /// by multiplying or dividing by a precomputed factor, power-of-two
+    /// resolutions by shifting - so there is no floating-point drift.
+    pub fn resolved_time(&self, interface: &InterfaceDescriptionBlock) -> (u64, u32) {
+        let mut tsresol = None;
+        let mut tsoffset = 0u64;
+
+        for opt in interface.options.iter() {
+            match *opt {
+                InterfaceDescriptionOption::TsResolution(v) => tsresol = Some(v),
+                InterfaceDescriptionOption::TsOffset(v) => tsoffset = v,
+                _ => {}
+            }
+        }
+
+        const NANOS_PER_SEC: u64 = 1_000_000_000;
+        let ticks = self.timestamp;
+
+        let (secs, nanos) = match tsresol {
+            // No if_tsresol: ticks are microseconds.
+            None => (ticks / 1_000_000, ((ticks % 1_000_000) * 1_000) as u32),
+
+            // MSB 0: resolution is 10^-bits seconds per tick. `bits` is
+            // attacker-controlled (the on-wire byte's low 7 bits, up to
+            // 127), so `pow10` itself saturates rather than overflowing.
+            Some(byte) if byte & 0x80 == 0 => {
+                let bits = (byte & 0x7F) as u32;
+
+                if bits <= 9 {
+                    // Same construction as the `None` branch above: split
+                    // into whole seconds and a remainder *before*
+                    // multiplying, so the multiplication can't overflow -
+                    // the remainder is always < 10^bits <= 10^9, and the
+                    // factor is always <= 10^9, so their product never
+                    // exceeds 10^18, well within a u64 (unlike multiplying
+                    // the untruncated `ticks` by the nanos-per-tick factor
+                    // directly, which silently saturates to the wrong,
+                    // far-too-small answer for large `ticks`).
+                    let ticks_per_sec = pow10(bits);
+                    let nanos_per_tick = pow10(9 - bits);
+
+                    (ticks / ticks_per_sec, ((ticks % ticks_per_sec) * nanos_per_tick) as u32)
+                } else {
+                    let ticks_per_nano = pow10(bits - 9);
+                    let total_nanos = ticks / ticks_per_nano;
+
+                    (total_nanos / NANOS_PER_SEC, (total_nanos % NANOS_PER_SEC) as u32)
+                }
+            }
+
+            // MSB 1: resolution is 2^-bits seconds per tick. `bits` (the
+            // low 7 bits of the on-wire byte) can reach 127, well past the
+            // 64-bit shifts below's valid range, so shifts by 64 or more
+            // are special-cased instead of attempted directly.
+            Some(byte) => {
+                let bits = (byte & 0x7F) as uint;
+
+                if bits >= 64 {
+                    (0, 0)
+                } else {
+                    let frac_ticks = ticks & ((1u64 << bits) - 1);
+
+                    // frac_ticks * NANOS_PER_SEC overflows a u64 once bits
+                    // exceeds 34, so past 32 bits multiply the two 32-bit
+                    // halves separately; each product stays below 2^62.
+                    let frac_nanos = if bits <= 32 {
+                        (frac_ticks * NANOS_PER_SEC) >> bits
+                    } else {
+                        let hi = (frac_ticks >> 32) * NANOS_PER_SEC;
+                        let lo = (frac_ticks & 0xFFFF_FFFF) * NANOS_PER_SEC;
+                        (hi + (lo >> 32)) >> (bits - 32)
+                    };
+
+                    (ticks >> bits, frac_nanos as u32)
+                }
+            }
+        };
+
+        (secs + tsoffset, nanos)
+    }
+}
+
+/// Computes `10^exponent` via repeated multiplication, saturating at
+/// `u64::MAX` instead of overflowing - `exponent` may come straight off the
+/// wire (as large as 118), far past what a `u64` can represent.
+fn pow10(exponent: u32) -> u64 {
+    let mut result = 1u64;
+
+    for _ in range(0, exponent) {
+        result = saturating_mul_u64(result, 10);
+    }
+
+    result
+}
+
+/// Multiplies two `u64`s, saturating at `u64::MAX` instead of wrapping or
+/// panicking on overflow.
+fn saturating_mul_u64(a: u64, b: u64) -> u64 {
+    if a == 0 || b == 0 {
+        0
+    } else if a > !0u64 / b {
+        !0u64
+    } else {
+        a * b
+    }
 }
 
 impl InterfaceStatisticsBlock {
-    pub fn read(r: &mut BufReader) -> Result<InterfaceStatisticsBlock, Error> {
-        let interface_id = try!(r.read_le_u32());
-        let ts = try!(r.read_le_u64());
+    pub fn read(r: &mut BufReader, endianness: Endianness) -> Result<InterfaceStatisticsBlock, Error> {
+        let interface_id = try!(endianness.read_u32(r));
+        let ts = try!(endianness.read_u64(r));
 
         let mut options = Vec::new();
 
         if !r.eof() {
             loop {
                 use InterfaceStatisticsOption::*;
-                let (code, data) = try!(read_option(r));
+                let (code, data) = try!(read_option(r, endianness));
 
                 let opt = match code {
                     0 => break,
@@ -414,7 +1380,7 @@ impl InterfaceStatisticsBlock {
                     }
 
                     2...8 => {
-                        let data = try!((&*data).read_le_u64());
+                        let data = try!(endianness.read_u64(&mut &*data));
 
                         match code {
                             2 => StartTime(data),
@@ -441,4 +1407,123 @@ impl InterfaceStatisticsBlock {
             options: options
         })
     }
-}
\ No newline at end of file
+
+    /// Serializes this block to bytes, including the generic block framing,
+    /// ready to append to a file.
+    pub fn to_bytes(&self, endianness: Endianness) -> Vec<u8> {
+        use InterfaceStatisticsOption::*;
+
+        let mut payload = Vec::new();
+        endianness.write_u32(&mut payload, self.interface_id).unwrap();
+        endianness.write_u64(&mut payload, self.timestamp).unwrap();
+
+        for opt in self.options.iter() {
+            let (code, data) = match *opt {
+                Comment(ref s) => (1u16, s.clone().into_bytes()),
+
+                StartTime(v) => (2u16, encode_u64(v, endianness)),
+                EndTime(v) => (3u16, encode_u64(v, endianness)),
+                Received(v) => (4u16, encode_u64(v, endianness)),
+                Dropped(v) => (5u16, encode_u64(v, endianness)),
+                FilterAccepted(v) => (6u16, encode_u64(v, endianness)),
+                OSDropped(v) => (7u16, encode_u64(v, endianness)),
+                Delivered(v) => (8u16, encode_u64(v, endianness))
+            };
+
+            payload.push_all(&*encode_option(code, &*data, endianness));
+        }
+        payload.push_all(&*encode_end_of_opt(endianness));
+
+        encode_block(block_type::INTERFACE_STATISTICS, &*payload, endianness)
+    }
+
+    /// Writes this block, including the generic block framing, to `w`.
+    pub fn write<W: Writer>(&self, w: &mut W, endianness: Endianness) -> Result<(), IoError> {
+        w.write(&*self.to_bytes(endianness))
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trips_a_big_endian_section() {
+        let shb = SectionHeaderBlock {
+            endianness: Endianness::Big,
+            magic: 0x1A2B3C4D,
+            major_version: 1,
+            minor_version: 0,
+            section_length: !0u64,
+            options: Vec::new()
+        };
+
+        let idb = InterfaceDescriptionBlock {
+            link_type: 1,
+            snap_len: 65535,
+            options: vec![InterfaceDescriptionOption::TsOffset(42)]
+        };
+
+        let mut writer = PcapNgWriter::new(Vec::new());
+        writer.write_block(&Block::SectionHeader(shb)).unwrap();
+        writer.write_block(&Block::InterfaceDescription(idb)).unwrap();
+        let buf = writer.writer;
+
+        let mut reader = PcapNgReader::new(BufReader::new(&*buf));
+
+        match reader.next_block().unwrap().unwrap() {
+            Block::SectionHeader(shb) => {
+                assert_eq!(shb.endianness, Endianness::Big);
+                assert_eq!(shb.magic, 0x1A2B3C4D);
+                assert_eq!(shb.major_version, 1);
+                assert_eq!(shb.minor_version, 0);
+                assert_eq!(shb.section_length, !0u64);
+            }
+            _ => panic!("expected the first block to be a SectionHeaderBlock")
+        }
+
+        match reader.next_block().unwrap().unwrap() {
+            Block::InterfaceDescription(idb) => {
+                assert_eq!(idb.link_type, 1);
+                assert_eq!(idb.snap_len, 65535);
+
+                match idb.options[0] {
+                    InterfaceDescriptionOption::TsOffset(v) => assert_eq!(v, 42),
+                    _ => panic!("expected the interface's only option to be TsOffset")
+                }
+            }
+            _ => panic!("expected the second block to be an InterfaceDescriptionBlock")
+        }
+
+        assert!(reader.next_block().unwrap().is_none());
+    }
+
+    #[test]
+    fn resolved_time_agrees_with_default_resolution_for_large_timestamps() {
+        let interface = InterfaceDescriptionBlock {
+            link_type: 1,
+            snap_len: 65535,
+            options: vec![InterfaceDescriptionOption::TsResolution(6)]
+        };
+
+        let default_interface = InterfaceDescriptionBlock {
+            link_type: 1,
+            snap_len: 65535,
+            options: Vec::new()
+        };
+
+        let packet = EnhancedPacketBlock {
+            interface_id: 0,
+            timestamp: !0u64,
+            captured_len: 0,
+            len: 0,
+            data: Vec::new(),
+            options: Vec::new()
+        };
+
+        // TsResolution(6) is microsecond resolution, the same as the
+        // implicit default, so both must agree - even at the top of the
+        // u64 range, where a naive ticks * nanos_per_tick multiplication
+        // overflows.
+        assert_eq!(packet.resolved_time(&interface), packet.resolved_time(&default_interface));
+    }
+}